@@ -1,6 +1,16 @@
+use std::io::{BufReader, Read};
 use std::path::Path;
 use tracing::trace;
 
+/// Chunk size used when streaming a file into a hasher instead of
+/// `fs::read`-ing the whole thing into memory.
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Prefix applied to a text hash when `prehash_limit` cut the read short, so
+/// a partial fingerprint can never be mistaken for (or collide in intent
+/// with) a hash of the full file.
+const PARTIAL_HASH_PREFIX: &str = "partial:";
+
 // Dual hashing system: semantic (p-hash) and text (xxhash)
 pub enum HashMode {
     Semantic,  // Protein-hash for understanding code's soul
@@ -13,64 +23,482 @@ pub struct DualHash {
     pub textual: String,   // xxhash: the body
 }
 
-/// Generate protein hash - semantic understanding of code
+/// Source languages the semantic hasher knows how to parse.
+///
+/// Each variant maps to a tree-sitter grammar; `Unknown` falls back to a
+/// best-effort guess so `protein_hash` still returns something useful when
+/// called without a file path to resolve an extension from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    JavaScript,
+    TypeScript,
+    Rust,
+    Go,
+    Java,
+    Python,
+    Unknown,
+}
+
+impl Language {
+    /// Resolve a language from a file extension (without the leading dot).
+    pub fn from_extension(ext: &str) -> Self {
+        match ext {
+            "js" | "jsx" | "mjs" | "cjs" => Language::JavaScript,
+            "ts" | "tsx" | "mts" | "cts" => Language::TypeScript,
+            "rs" => Language::Rust,
+            "go" => Language::Go,
+            "java" => Language::Java,
+            "py" => Language::Python,
+            _ => Language::Unknown,
+        }
+    }
+
+    fn grammar(self) -> Option<tree_sitter::Language> {
+        match self {
+            Language::JavaScript => Some(tree_sitter_javascript::language()),
+            // Both `.ts` and `.tsx` files resolve here and use the plain
+            // `language_typescript` grammar, not the TSX one.
+            Language::TypeScript => Some(tree_sitter_typescript::language_typescript()),
+            Language::Rust => Some(tree_sitter_rust::language()),
+            Language::Go => Some(tree_sitter_go::language()),
+            Language::Java => Some(tree_sitter_java::language()),
+            Language::Python => Some(tree_sitter_python::language()),
+            Language::Unknown => None,
+        }
+    }
+
+    /// Cheap content sniff used when no file path (and therefore no
+    /// extension) is available, e.g. the array/string hashing entry points.
+    fn sniff(content: &str) -> Self {
+        if content.contains("fn ") && content.contains("->") {
+            Language::Rust
+        } else if content.contains("def ") && content.contains(':') {
+            Language::Python
+        } else if content.contains("func ") && content.contains("package ") {
+            Language::Go
+        } else {
+            // Historically this hasher only ever understood JS-shaped code;
+            // keep that as the fallback so untyped callers behave the same.
+            Language::JavaScript
+        }
+    }
+}
+
+/// Canonical, language-agnostic buckets that AST node kinds fold into.
+///
+/// The whole point of hashing off these instead of raw node kinds is that
+/// `const add = (a, b) => a + b` (an `arrow_function`) and
+/// `function add(a, b) { return a + b; }` (a `function_declaration`) both
+/// land in `FunctionLike`, so semantically equivalent code in different
+/// syntactic clothing produces the same feature vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum FeatureKind {
+    FunctionLike = 0,
+    IfLike = 1,
+    LoopLike = 2,
+    SwitchLike = 3,
+    CallLike = 4,
+    ImportLike = 5,
+    ExportLike = 6,
+    ClassLike = 7,
+    VariableLike = 8,
+    TryLike = 9,
+    ReturnLike = 10,
+}
+
+/// Fixed, stable ordering of `FeatureKind` — this *is* the feature-vector
+/// layout. Never reorder or remove a variant; append new ones at the end.
+const FEATURE_KINDS: [FeatureKind; 11] = [
+    FeatureKind::FunctionLike,
+    FeatureKind::IfLike,
+    FeatureKind::LoopLike,
+    FeatureKind::SwitchLike,
+    FeatureKind::CallLike,
+    FeatureKind::ImportLike,
+    FeatureKind::ExportLike,
+    FeatureKind::ClassLike,
+    FeatureKind::VariableLike,
+    FeatureKind::TryLike,
+    FeatureKind::ReturnLike,
+];
+
+impl FeatureKind {
+    /// Map a grammar-specific tree-sitter node kind to its canonical bucket.
+    /// Unmapped kinds (identifiers, punctuation, literals, ...) return `None`
+    /// and are skipped by the walker.
+    fn from_node_kind(kind: &str) -> Option<FeatureKind> {
+        match kind {
+            "function_declaration" | "function_expression" | "arrow_function"
+            | "method_definition" | "function_item" | "function_definition"
+            | "func_literal" => Some(FeatureKind::FunctionLike),
+
+            "if_statement" | "if_expression" | "if_let_expression" => Some(FeatureKind::IfLike),
+
+            "for_statement" | "while_statement" | "for_in_statement" | "for_of_statement"
+            | "loop_expression" | "while_expression" | "do_statement" => {
+                Some(FeatureKind::LoopLike)
+            }
+
+            "switch_statement" | "match_expression" | "match_statement" => {
+                Some(FeatureKind::SwitchLike)
+            }
+
+            "call_expression" | "call" => Some(FeatureKind::CallLike),
+
+            "import_statement" | "import_declaration" | "use_declaration" => {
+                Some(FeatureKind::ImportLike)
+            }
+
+            "export_statement" => Some(FeatureKind::ExportLike),
+
+            "class_declaration" | "class_definition" | "struct_item" | "impl_item" => {
+                Some(FeatureKind::ClassLike)
+            }
+
+            "variable_declarator" | "let_declaration" | "short_var_declaration" => {
+                Some(FeatureKind::VariableLike)
+            }
+
+            "try_statement" => Some(FeatureKind::TryLike),
+
+            "return_statement" => Some(FeatureKind::ReturnLike),
+
+            _ => None,
+        }
+    }
+
+    /// Classify a node the walker is visiting, possibly into more than one
+    /// bucket. This is a thin wrapper over `from_node_kind` with two
+    /// special cases, both in service of the same guarantee: a function
+    /// bound to a name collapses to the same vector as a bare declaration.
+    ///
+    /// - Name bindings (`variable_declarator`, `let_declaration`,
+    ///   `short_var_declaration`): when the bound value is itself a function
+    ///   (`const add = (a, b) => ...`), the binding contributes nothing, so
+    ///   it doesn't pick up an extra `VariableLike` count that the
+    ///   declaration form (`function add(a, b) { ... }`) never gets.
+    /// - Expression-bodied arrows (`(a, b) => a + b`): the body has no
+    ///   `return_statement` node, unlike a block-bodied function's
+    ///   `return a + b;`, so it contributes an implicit `ReturnLike` to
+    ///   match what the equivalent declaration form counts.
+    fn classify(node: tree_sitter::Node) -> Vec<FeatureKind> {
+        match node.kind() {
+            "variable_declarator" | "let_declaration" | "short_var_declaration" => {
+                let is_function = |n: tree_sitter::Node| {
+                    FeatureKind::from_node_kind(n.kind()) == Some(FeatureKind::FunctionLike)
+                };
+                let mut cursor = node.walk();
+                // Go wraps both sides of `:=` in an `expression_list`, so the
+                // bound value is looked for one level down there.
+                let binds_function = node.named_children(&mut cursor).any(|child| {
+                    if child.kind() == "expression_list" {
+                        let mut inner = child.walk();
+                        let found = child.named_children(&mut inner).any(is_function);
+                        found
+                    } else {
+                        is_function(child)
+                    }
+                });
+
+                if binds_function {
+                    Vec::new()
+                } else {
+                    vec![FeatureKind::VariableLike]
+                }
+            }
+
+            "arrow_function" => {
+                let mut kinds = vec![FeatureKind::FunctionLike];
+                let has_block_body = node
+                    .child_by_field_name("body")
+                    .map(|body| body.kind() == "statement_block")
+                    .unwrap_or(false);
+                if !has_block_body {
+                    kinds.push(FeatureKind::ReturnLike);
+                }
+                kinds
+            }
+
+            kind => FeatureKind::from_node_kind(kind).into_iter().collect(),
+        }
+    }
+}
+
+/// Walk a parsed tree-sitter tree and accumulate per-`FeatureKind` counts,
+/// in the fixed order of `FEATURE_KINDS`.
+fn extract_feature_vector(source: &[u8], language: Language) -> [u32; FEATURE_KINDS.len()] {
+    let mut counts = [0u32; FEATURE_KINDS.len()];
+
+    let Some(grammar) = language.grammar() else {
+        return counts;
+    };
+
+    let mut parser = tree_sitter::Parser::new();
+    if parser.set_language(&grammar).is_err() {
+        return counts;
+    }
+
+    let Some(tree) = parser.parse(source, None) else {
+        return counts;
+    };
+
+    let mut cursor = tree.walk();
+    let mut reached_root = false;
+
+    loop {
+        for kind in FeatureKind::classify(cursor.node()) {
+            counts[kind as usize] += 1;
+        }
+
+        if cursor.goto_first_child() {
+            continue;
+        }
+
+        loop {
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                reached_root = true;
+                break;
+            }
+        }
+
+        if reached_root {
+            break;
+        }
+    }
+
+    counts
+}
+
+/// Generate protein hash - semantic understanding of code.
+///
+/// No file extension is available here, so the language is guessed from the
+/// content itself (see `Language::sniff`). Prefer `hash_file_path_harmonized`
+/// when a path is on hand, since it resolves the language precisely.
 pub fn protein_hash(content: &[u8]) -> String {
-    // For now, use a simplified semantic hash
-    // This will be replaced with actual protein-hash-v2 integration
     let text = String::from_utf8_lossy(content);
-    
-    // Extract semantic features
-    let mut features = Vec::new();
-    
-    // Count function patterns
-    let functions = text.matches("function").count() + 
-                   text.matches("=>").count() +
-                   text.matches("async").count();
-    features.push(functions as u32);
-    
-    // Count control flow
-    let control = text.matches("if").count() +
-                 text.matches("for").count() +
-                 text.matches("while").count() +
-                 text.matches("switch").count();
-    features.push(control as u32);
-    
-    // Count data operations
-    let data = text.matches("map").count() +
-              text.matches("filter").count() +
-              text.matches("reduce").count() +
-              text.matches("forEach").count();
-    features.push(data as u32);
-    
-    // Count imports/exports
-    let modules = text.matches("import").count() +
-                 text.matches("export").count() +
-                 text.matches("require").count();
-    features.push(modules as u32);
-    
-    // Generate p-hash from features
-    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
-    hasher.update(b"PROTEIN:");
-    for feature in features {
-        hasher.update(&feature.to_le_bytes());
-    }
-    
-    format!("p{:016x}", hasher.digest())
+    let language = Language::sniff(&text);
+    protein_hash_with_language(content, language)
+}
+
+/// Same as `protein_hash`, but the language is already known (resolved from
+/// a file extension) instead of guessed from content. `Language::Unknown`
+/// has no grammar, so it falls back to the same content sniff as `protein_hash`.
+pub fn protein_hash_with_language(content: &[u8], language: Language) -> String {
+    let language = match language {
+        Language::Unknown => Language::sniff(&String::from_utf8_lossy(content)),
+        known => known,
+    };
+    let features = extract_feature_vector(content, language);
+    format!("p{:016x}", simhash(&features))
+}
+
+/// Version of the protein-hash spec: the fixed `FEATURE_KINDS` ordering, the
+/// feature-vector layout it produces, and the `PROTEIN_SPEC_PREFIX`/seed
+/// used below. Any change to any of those is a new spec version, so other
+/// implementations can tell whether they're reproducing the same hash space.
+const PROTEIN_SPEC_VERSION: u32 = 1;
+
+/// Fixed prefix folded into every per-feature hash. Pinning this (rather
+/// than leaving it as an implicit default) is what makes the hash
+/// reproducible by an independent implementation given only this spec.
+const PROTEIN_SPEC_PREFIX: &[u8] = b"PROTEIN:v1:";
+
+/// The protein-hash spec version implemented by this build. An external
+/// implementation can compare this against its own to know whether it's
+/// safe to compare p-hashes byte-for-byte (or by Hamming distance).
+#[napi]
+pub fn protein_hash_spec_version() -> u32 {
+    PROTEIN_SPEC_VERSION
+}
+
+/// Fold a feature vector into a 64-bit SimHash so that structurally similar
+/// code (similar feature counts) lands on hashes with a small Hamming
+/// distance, instead of requiring byte-for-byte identity.
+///
+/// For each feature index `i` with count `c_i`, derive a stable per-feature
+/// hash `h_i = xxh3(PROTEIN_SPEC_PREFIX ++ (i as u32).to_le_bytes())`, then for every
+/// bit `b` of `h_i` add `c_i` to an accumulator if the bit is set, or
+/// subtract it otherwise. The resulting hash has bit `b` set iff the
+/// accumulator ended up positive. `FEATURE_KINDS`' order, this prefix, and
+/// this algorithm together make up the spec reported by
+/// `protein_hash_spec_version`.
+fn simhash(features: &[u32]) -> u64 {
+    let mut acc = [0i64; 64];
+
+    for (i, &count) in features.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+
+        let mut seed = Vec::with_capacity(PROTEIN_SPEC_PREFIX.len() + 4);
+        seed.extend_from_slice(PROTEIN_SPEC_PREFIX);
+        seed.extend_from_slice(&(i as u32).to_le_bytes());
+        let h_i = xxhash_rust::xxh3::xxh3_64(&seed);
+
+        for (b, slot) in acc.iter_mut().enumerate() {
+            if (h_i >> b) & 1 == 1 {
+                *slot += count as i64;
+            } else {
+                *slot -= count as i64;
+            }
+        }
+    }
+
+    let mut result: u64 = 0;
+    for (b, &slot) in acc.iter().enumerate() {
+        if slot > 0 {
+            result |= 1 << b;
+        }
+    }
+    result
+}
+
+/// Common interface over the textual hashing backends, so `text_hash` and
+/// friends don't have to special-case each algorithm's update/finalize API.
+trait NxHasher {
+    fn update(&mut self, bytes: &[u8]);
+    fn finalize(self: Box<Self>) -> String;
+}
+
+impl NxHasher for xxhash_rust::xxh3::Xxh3 {
+    fn update(&mut self, bytes: &[u8]) {
+        xxhash_rust::xxh3::Xxh3::update(self, bytes);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        // Decimal, not hex: matches the pre-existing `text_hash` output so
+        // routing through the factory doesn't change values already relied
+        // on for cache/identity keys.
+        self.digest().to_string()
+    }
+}
+
+impl NxHasher for blake3::Hasher {
+    fn update(&mut self, bytes: &[u8]) {
+        blake3::Hasher::update(self, bytes);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        blake3::Hasher::finalize(&self).to_hex().to_string()
+    }
+}
+
+impl NxHasher for crc32fast::Hasher {
+    fn update(&mut self, bytes: &[u8]) {
+        crc32fast::Hasher::update(self, bytes);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        format!("{:08x}", crc32fast::Hasher::finalize(*self))
+    }
+}
+
+/// Selectable textual hashing backend. `Xxh3` is the long-standing default
+/// (fast, non-cryptographic); `Blake3` is for callers that need a
+/// cryptographically verifiable digest; `Crc32` is for cheap "did this
+/// change" checks where collision resistance doesn't matter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextHashType {
+    Xxh3,
+    Blake3,
+    Crc32,
+}
+
+impl TextHashType {
+    fn hasher(self) -> Box<dyn NxHasher> {
+        match self {
+            TextHashType::Xxh3 => Box::new(xxhash_rust::xxh3::Xxh3::new()),
+            TextHashType::Blake3 => Box::new(blake3::Hasher::new()),
+            TextHashType::Crc32 => Box::new(crc32fast::Hasher::new()),
+        }
+    }
+
+    fn from_name(name: &str) -> Option<TextHashType> {
+        match name.to_ascii_lowercase().as_str() {
+            "xxh3" | "xxhash" => Some(TextHashType::Xxh3),
+            "blake3" => Some(TextHashType::Blake3),
+            "crc32" => Some(TextHashType::Crc32),
+            _ => None,
+        }
+    }
+}
+
+/// Text hash using a chosen backend (see `TextHashType`).
+pub fn text_hash_with(content: &[u8], hash_type: TextHashType) -> String {
+    let mut hasher = hash_type.hasher();
+    hasher.update(content);
+    hasher.finalize()
 }
 
 /// Original xxhash for text identity
 pub fn text_hash(content: &[u8]) -> String {
-    xxhash_rust::xxh3::xxh3_64(content).to_string()
+    text_hash_with(content, TextHashType::Xxh3)
 }
 
-/// Harmonized hash function that can switch modes
-pub fn hash(content: &[u8], mode: HashMode) -> String {
+/// Text-hash a reader in fixed-size chunks instead of buffering the whole
+/// input, so large files don't need to be fully loaded into memory just to
+/// compute a change signal.
+///
+/// `prehash_limit`, when set, stops reading after the first N bytes and
+/// returns a `partial:`-prefixed hash of just that prefix — a fast,
+/// approximate fingerprint rather than a hash of the whole file. Semantic
+/// hashing has no equivalent: parsing an AST needs the complete content, so
+/// the limit only ever applies to this textual path.
+pub fn text_hash_streaming<R: Read>(
+    mut reader: R,
+    hash_type: TextHashType,
+    prehash_limit: Option<u64>,
+) -> std::io::Result<String> {
+    let mut hasher = hash_type.hasher();
+    let mut buf = [0u8; STREAM_CHUNK_SIZE];
+    let mut read_total: u64 = 0;
+    let mut truncated = false;
+
+    loop {
+        let remaining = prehash_limit.map(|limit| limit.saturating_sub(read_total));
+        if remaining == Some(0) {
+            // Exactly hit the limit - peek one more byte to tell "the file
+            // is precisely this long" from "there's more we didn't read".
+            let mut probe = [0u8; 1];
+            truncated = reader.read(&mut probe)? > 0;
+            break;
+        }
+
+        let want = remaining
+            .map(|r| r.min(STREAM_CHUNK_SIZE as u64) as usize)
+            .unwrap_or(STREAM_CHUNK_SIZE);
+
+        let n = reader.read(&mut buf[..want])?;
+        if n == 0 {
+            break;
+        }
+
+        hasher.update(&buf[..n]);
+        read_total += n as u64;
+    }
+
+    let digest = hasher.finalize();
+    Ok(if truncated {
+        format!("{}{}", PARTIAL_HASH_PREFIX, digest)
+    } else {
+        digest
+    })
+}
+
+/// Harmonized hash function that can switch modes. `HashMode::Text` and
+/// `HashMode::Dual` pair with a `TextHashType` so the textual backend is
+/// selectable independently of whether the semantic hash is also computed.
+pub fn hash(content: &[u8], mode: HashMode, text_hash_type: TextHashType) -> String {
     match mode {
         HashMode::Semantic => protein_hash(content),
-        HashMode::Text => text_hash(content),
+        HashMode::Text => text_hash_with(content, text_hash_type),
         HashMode::Dual => {
             let p = protein_hash(content);
-            let t = text_hash(content);
+            let t = text_hash_with(content, text_hash_type);
             format!("{}:{}", p, t)
         }
     }
@@ -80,9 +508,9 @@ pub fn hash(content: &[u8], mode: HashMode) -> String {
 pub fn auto_hash(content: &[u8]) -> String {
     // Detect if content is code or data
     let text = String::from_utf8_lossy(content);
-    
+
     // If it looks like code, use semantic hash
-    if text.contains("function") || text.contains("class") || 
+    if text.contains("function") || text.contains("class") ||
        text.contains("import") || text.contains("const") ||
        text.contains("=>") || text.contains("async") {
         protein_hash(content)
@@ -93,7 +521,11 @@ pub fn auto_hash(content: &[u8]) -> String {
 }
 
 #[napi]
-pub fn hash_array_harmonized(input: Vec<Option<String>>, semantic: bool) -> String {
+pub fn hash_array_harmonized(
+    input: Vec<Option<String>>,
+    semantic: bool,
+    text_hash_type: Option<String>,
+) -> String {
     let joined = input
         .iter()
         .filter_map(|s| {
@@ -104,13 +536,17 @@ pub fn hash_array_harmonized(input: Vec<Option<String>>, semantic: bool) -> Stri
         })
         .collect::<Vec<_>>()
         .join(",");
-    
+
     let content = joined.as_bytes();
-    
+
     if semantic {
         protein_hash(content)
     } else {
-        text_hash(content)
+        let hash_type = text_hash_type
+            .as_deref()
+            .and_then(TextHashType::from_name)
+            .unwrap_or(TextHashType::Xxh3);
+        text_hash_with(content, hash_type)
     }
 }
 
@@ -121,45 +557,86 @@ pub fn hash_file_harmonized(file: String, semantic: bool) -> Option<String> {
 
 #[inline]
 pub fn hash_file_path_harmonized<P: AsRef<Path>>(path: P, semantic: bool) -> Option<String> {
+    hash_file_path_harmonized_with_limit(path, semantic, None)
+}
+
+/// Same as `hash_file_path_harmonized`, with an optional `prehash_limit` on
+/// the textual path (see `text_hash_streaming`). Semantic hashing always
+/// reads the whole file - a partial AST isn't meaningful - so the limit is
+/// silently ignored whenever `semantic` resolves to an actual AST parse.
+pub fn hash_file_path_harmonized_with_limit<P: AsRef<Path>>(
+    path: P,
+    semantic: bool,
+    prehash_limit: Option<u64>,
+) -> Option<String> {
     let path = path.as_ref();
     trace!("Reading {:?} to hash", path);
-    
-    let Ok(content) = std::fs::read(path) else {
-        trace!("Failed to read file: {:?}", path);
-        return None;
-    };
-    
-    trace!("Hashing {:?} with mode: {}", path, if semantic { "semantic" } else { "text" });
-    
-    let hash = if semantic {
-        // For code files, use semantic hash
-        if path.extension()
-            .and_then(|e| e.to_str())
-            .map(|e| matches!(e, "js" | "ts" | "jsx" | "tsx" | "rs" | "go" | "java" | "py"))
-            .unwrap_or(false) {
-            protein_hash(&content)
-        } else {
-            text_hash(&content)
-        }
+
+    let language = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(Language::from_extension)
+        .unwrap_or(Language::Unknown);
+
+    let hash = if semantic && language != Language::Unknown {
+        let Ok(content) = std::fs::read(path) else {
+            trace!("Failed to read file: {:?}", path);
+            return None;
+        };
+        protein_hash_with_language(&content, language)
     } else {
-        text_hash(&content)
+        let Ok(file) = std::fs::File::open(path) else {
+            trace!("Failed to read file: {:?}", path);
+            return None;
+        };
+        let Ok(hash) =
+            text_hash_streaming(BufReader::new(file), TextHashType::Xxh3, prehash_limit)
+        else {
+            trace!("Failed to stream file: {:?}", path);
+            return None;
+        };
+        hash
     };
-    
+
+    trace!("Hashing {:?} with mode: {}", path, if semantic { "semantic" } else { "text" });
     trace!("Hashed file {:?} - {:?}", path, hash);
     Some(hash)
 }
 
+/// Hash a file without fully buffering it, optionally stopping after
+/// `prehash_limit` bytes for a fast partial fingerprint on the textual path.
+#[napi]
+pub fn hash_file_streaming(
+    file: String,
+    semantic: bool,
+    prehash_limit: Option<i64>,
+) -> Option<String> {
+    let limit = prehash_limit.map(|n| n.max(0) as u64);
+    hash_file_path_harmonized_with_limit(file, semantic, limit)
+}
+
 /// Generate dual hash for complete identity
 #[napi]
-pub fn dual_hash_file(file: String) -> Option<DualHashResult> {
+pub fn dual_hash_file(file: String, text_hash_type: Option<String>) -> Option<DualHashResult> {
     let path = Path::new(&file);
     let Ok(content) = std::fs::read(path) else {
         return None;
     };
-    
+
+    let language = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(Language::from_extension)
+        .unwrap_or(Language::Unknown);
+
+    let hash_type = text_hash_type
+        .as_deref()
+        .and_then(TextHashType::from_name)
+        .unwrap_or(TextHashType::Xxh3);
+
     Some(DualHashResult {
-        semantic: protein_hash(&content),
-        textual: text_hash(&content),
+        semantic: protein_hash_with_language(&content, language),
+        textual: text_hash_with(&content, hash_type),
     })
 }
 
@@ -174,55 +651,246 @@ pub fn find_soul_siblings(p_hash: &str, registry: &SoulRegistry) -> Vec<String>
     registry.find_by_soul(p_hash)
 }
 
+/// A registry of p-hash <-> path and text-hash <-> path relationships.
+///
+/// Serializable so it can be written to disk and reloaded between runs (or
+/// handed to an external tool) instead of being rebuilt from scratch every
+/// time - see `save`/`load`.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
 pub struct SoulRegistry {
     // Maps p-hash to list of file paths with same semantic soul
     souls: std::collections::HashMap<String, Vec<String>>,
+    // Reverse index: text-hash to list of file paths with identical bytes
+    texts: std::collections::HashMap<String, Vec<String>>,
 }
 
 impl SoulRegistry {
     pub fn new() -> Self {
-        Self {
-            souls: std::collections::HashMap::new(),
-        }
+        Self::default()
     }
-    
-    pub fn register(&mut self, p_hash: String, path: String) {
-        self.souls.entry(p_hash).or_insert_with(Vec::new).push(path);
+
+    /// Register a path under both its semantic (p-hash) and textual
+    /// identity, so it's reachable from either `find_by_soul`/`find_similar`
+    /// or `find_by_text`.
+    pub fn register(&mut self, p_hash: String, text_hash: String, path: String) {
+        self.souls.entry(p_hash).or_insert_with(Vec::new).push(path.clone());
+        self.texts.entry(text_hash).or_insert_with(Vec::new).push(path);
     }
-    
+
     pub fn find_by_soul(&self, p_hash: &str) -> Vec<String> {
         self.souls.get(p_hash).cloned().unwrap_or_default()
     }
+
+    /// Find paths whose p-hash is within `max_distance` Hamming bits of
+    /// `p_hash`, sorted by ascending distance. Unlike `find_by_soul` (exact
+    /// match only), this is what makes the registry an approximate clone
+    /// detector: SimHash guarantees similar code clusters near each other
+    /// in Hamming space, not just at distance zero.
+    pub fn find_similar(&self, p_hash: &str, max_distance: u32) -> Vec<(String, u32)> {
+        let Some(query) = parse_p_hash(p_hash) else {
+            return Vec::new();
+        };
+
+        let mut matches: Vec<(String, u32)> = self
+            .souls
+            .iter()
+            .filter_map(|(stored, paths)| {
+                let distance = (parse_p_hash(stored)? ^ query).count_ones();
+                (distance <= max_distance).then_some((paths, distance))
+            })
+            .flat_map(|(paths, distance)| paths.iter().map(move |path| (path.clone(), distance)))
+            .collect();
+
+        matches.sort_by_key(|(_, distance)| *distance);
+        matches
+    }
+
+    /// Find paths with an identical text-hash - a byte-for-byte duplicate.
+    pub fn find_by_text(&self, text_hash: &str) -> Vec<String> {
+        self.texts.get(text_hash).cloned().unwrap_or_default()
+    }
+
+    /// Persist the registry as JSON so it can be reloaded with `load`, or
+    /// read by a tool in another language that only needs to understand the
+    /// `p-hash -> paths` / `text-hash -> paths` map shape.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Reload a registry previously written by `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        serde_json::from_reader(file)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Parse the `p{:016x}` string format back into the raw 64-bit SimHash.
+fn parse_p_hash(p_hash: &str) -> Option<u64> {
+    u64::from_str_radix(p_hash.strip_prefix('p')?, 16).ok()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
     fn test_protein_hash_recognizes_similar_code() {
         let code1 = b"function add(a, b) { return a + b; }";
         let code2 = b"const add = (a, b) => a + b;";
         let code3 = b"function sum(x, y) { return x + y; }";
-        
+
         let p1 = protein_hash(code1);
         let p2 = protein_hash(code2);
         let p3 = protein_hash(code3);
-        
+
         // Similar semantic structure should produce similar hashes
         // (In real protein-hash, these would be closer)
         println!("p1: {}, p2: {}, p3: {}", p1, p2, p3);
     }
-    
+
+    #[test]
+    fn test_protein_hash_ignores_syntax_shape() {
+        // Same semantic shape (one function, one implicit/explicit return),
+        // different surface syntax, including an expression-bodied arrow
+        // with no `return_statement` node at all -> identical feature
+        // vector -> identical hash.
+        let function_decl = b"function add(a, b) { return a + b; }";
+        let expression_arrow = b"const add = (a, b) => a + b;";
+
+        assert_eq!(
+            protein_hash_with_language(function_decl, Language::JavaScript),
+            protein_hash_with_language(expression_arrow, Language::JavaScript)
+        );
+    }
+
+    #[test]
+    fn test_registry_find_similar_matches_within_hamming_distance() {
+        let mut registry = SoulRegistry::new();
+
+        let original = protein_hash_with_language(
+            b"function add(a, b) { return a + b; }",
+            Language::JavaScript,
+        );
+        let near_miss = protein_hash_with_language(
+            b"function add(a, b) { if (a) { return a + b; } }",
+            Language::JavaScript,
+        );
+        let unrelated = protein_hash_with_language(b"import x from 'y';", Language::JavaScript);
+
+        registry.register(original.clone(), text_hash(b"original"), "original.js".to_string());
+        registry.register(near_miss, text_hash(b"near_miss"), "near_miss.js".to_string());
+        registry.register(unrelated, text_hash(b"unrelated"), "unrelated.js".to_string());
+
+        let matches = registry.find_similar(&original, 64);
+        assert!(matches.iter().any(|(path, _)| path == "original.js"));
+        assert_eq!(matches[0].1, 0); // exact self-match sorts first
+    }
+
+    #[test]
+    fn test_registry_find_by_text_returns_exact_duplicates() {
+        let mut registry = SoulRegistry::new();
+        let content = b"const x = 1;";
+        let t_hash = text_hash(content);
+        let p_hash = protein_hash_with_language(content, Language::JavaScript);
+
+        registry.register(p_hash, t_hash.clone(), "a.js".to_string());
+        registry.register(
+            protein_hash_with_language(b"const y = 2;", Language::JavaScript),
+            text_hash(b"const y = 2;"),
+            "b.js".to_string(),
+        );
+
+        assert_eq!(registry.find_by_text(&t_hash), vec!["a.js".to_string()]);
+    }
+
+    #[test]
+    fn test_registry_save_and_load_round_trips() {
+        let mut registry = SoulRegistry::new();
+        let content = b"const x = 1;";
+        registry.register(
+            protein_hash_with_language(content, Language::JavaScript),
+            text_hash(content),
+            "a.js".to_string(),
+        );
+
+        let path = std::env::temp_dir().join("nx_soul_registry_test.json");
+        registry.save(&path).unwrap();
+        let loaded = SoulRegistry::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let p_hash = protein_hash_with_language(content, Language::JavaScript);
+        assert_eq!(loaded.find_by_soul(&p_hash), vec!["a.js".to_string()]);
+        assert_eq!(
+            loaded.find_by_text(&text_hash(content)),
+            vec!["a.js".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_protein_hash_spec_version_is_pinned() {
+        assert_eq!(protein_hash_spec_version(), 1);
+    }
+
     #[test]
     fn test_dual_hash_preserves_both_identities() {
         let content = b"const map = (arr, fn) => arr.map(fn);";
-        
-        let dual = hash(content, HashMode::Dual);
+
+        let dual = hash(content, HashMode::Dual, TextHashType::Xxh3);
         assert!(dual.contains(':'));
-        
+
         let parts: Vec<&str> = dual.split(':').collect();
         assert_eq!(parts.len(), 2);
         assert!(parts[0].starts_with('p')); // protein hash prefix
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_text_hash_backends_are_distinct_and_stable() {
+        let content = b"const map = (arr, fn) => arr.map(fn);";
+
+        let xxh3 = text_hash_with(content, TextHashType::Xxh3);
+        let blake3 = text_hash_with(content, TextHashType::Blake3);
+        let crc32 = text_hash_with(content, TextHashType::Crc32);
+
+        assert_ne!(xxh3, blake3);
+        assert_ne!(xxh3, crc32);
+        assert_eq!(xxh3, text_hash(content)); // text_hash defaults to xxh3
+        assert_eq!(blake3.len(), 64); // blake3 hex digest is 32 bytes
+        assert_eq!(crc32.len(), 8); // crc32 hex digest is 4 bytes
+    }
+
+    #[test]
+    fn test_streaming_hash_matches_full_buffer_hash() {
+        let content = b"const map = (arr, fn) => arr.map(fn);".repeat(1000);
+
+        let streamed =
+            text_hash_streaming(content.as_slice(), TextHashType::Xxh3, None).unwrap();
+        assert_eq!(streamed, text_hash(&content));
+    }
+
+    #[test]
+    fn test_prehash_limit_produces_distinct_partial_hash() {
+        let content = b"const map = (arr, fn) => arr.map(fn);".repeat(1000);
+
+        let partial = text_hash_streaming(content.as_slice(), TextHashType::Xxh3, Some(16))
+            .unwrap();
+        assert!(partial.starts_with("partial:"));
+
+        let full = text_hash_streaming(content.as_slice(), TextHashType::Xxh3, None).unwrap();
+        assert!(!full.starts_with("partial:"));
+        assert_ne!(partial, full);
+    }
+
+    #[test]
+    fn test_prehash_limit_at_exact_length_is_not_partial() {
+        let content = b"const map = () => {};";
+
+        let hash =
+            text_hash_streaming(content.as_slice(), TextHashType::Xxh3, Some(content.len() as u64))
+                .unwrap();
+        assert!(!hash.starts_with("partial:"));
+        assert_eq!(hash, text_hash(content));
+    }
+}